@@ -0,0 +1,164 @@
+use clap::Parser;
+use indicatif::{MultiProgress, ProgressDrawTarget};
+use parking_lot::Mutex;
+use std::path::PathBuf;
+use std::time::Instant;
+use tempfile::TempDir;
+
+use crate::{
+    clone_repository, collect_repository, parse_schema_version, Args, FileContent, OutputFormat,
+    ProcessingStats, RepoType, RepodError,
+};
+
+/// What [`Repod::process`] bundles.
+#[derive(Debug, Clone)]
+pub enum Input {
+    /// A local directory, read in place.
+    Path(PathBuf),
+    /// A git URL (https://, ssh:// or git@), cloned into a temporary directory first.
+    Url(String),
+}
+
+/// One assembled bundle.
+#[derive(Debug)]
+pub struct Bundle {
+    /// The tree and file blocks exactly as the CLI would write them.
+    pub content: String,
+    /// Included files in output order, README first.
+    pub files: Vec<FileContent>,
+    pub stats: ProcessingStats,
+}
+
+/// Embeddable entry point: configure the filters once, then bundle any number of inputs.
+///
+/// ```no_run
+/// use repod::{Input, OutputFormat, Repod};
+///
+/// let bundle = Repod::new()
+///     .exclude(["tests/"])
+///     .format(OutputFormat::Markdown)
+///     .max_tokens(100_000)
+///     .process(Input::Path(".".into()))?;
+/// println!("{} tokens", bundle.stats.total_tokens);
+/// # Ok::<(), repod::RepodError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Repod {
+    exclude: Vec<String>,
+    only: Vec<String>,
+    only_dirs: Vec<String>,
+    repo_types: Vec<RepoType>,
+    format: OutputFormat,
+    schema_version: Option<u32>,
+    max_tokens: Option<usize>,
+}
+
+impl Repod {
+    pub fn new() -> Repod {
+        Repod::default()
+    }
+
+    /// Add exclude globs, as with `--exclude`.
+    pub fn exclude<I, S>(mut self, patterns: I) -> Repod
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add include globs, as with `--only`.
+    pub fn only<I, S>(mut self, patterns: I) -> Repod
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.only.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Keep only files under these directories, as with `--only-dir`.
+    pub fn only_dirs<I, S>(mut self, dirs: I) -> Repod
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.only_dirs.extend(dirs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Keep only files of these languages, as with `--repo-types`.
+    pub fn repo_types(mut self, types: impl IntoIterator<Item = RepoType>) -> Repod {
+        self.repo_types.extend(types);
+        self
+    }
+
+    pub fn format(mut self, format: OutputFormat) -> Repod {
+        self.format = format;
+        self
+    }
+
+    /// Write [`OutputFormat::Json`] as the versioned document, as with `--schema-version`.
+    pub fn schema_version(mut self, version: u32) -> Repod {
+        self.schema_version = Some(version);
+        self
+    }
+
+    /// Leave out files once the bundle would exceed `tokens`, as with `--max-tokens`.
+    pub fn max_tokens(mut self, tokens: usize) -> Repod {
+        self.max_tokens = Some(tokens);
+        self
+    }
+
+    pub fn process(&self, input: Input) -> Result<Bundle, RepodError> {
+        let mut args = Args::parse_from(["repod"]);
+        args.exclude = self.exclude.clone();
+        args.only = self.only.clone();
+        args.only_dirs = self.only_dirs.clone();
+        args.repo_types = self.repo_types.clone();
+        args.format = self.format;
+        if let Some(version) = self.schema_version {
+            if self.format != OutputFormat::Json {
+                return Err(anyhow::anyhow!("schema versions apply to OutputFormat::Json").into());
+            }
+            args.schema_version =
+                Some(parse_schema_version(&version.to_string()).map_err(anyhow::Error::msg)?);
+        }
+        args.max_tokens = self.max_tokens;
+        args.read_token_env();
+
+        let stats = Mutex::new(ProcessingStats::default());
+        let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+
+        let mut _tmp: Option<TempDir> = None;
+        let (local, repo_dir, repo) = match input {
+            Input::Path(path) => (true, path, None),
+            Input::Url(url) => {
+                let clone_start = Instant::now();
+                let tmp = TempDir::new()?;
+                let path = tmp.path().to_path_buf();
+                let repo = clone_repository(&url, &path, &args, &multi_progress)?;
+                _tmp = Some(tmp);
+                let mut stats_guard = stats.lock();
+                stats_guard.repo_count += 1;
+                stats_guard.clone_time += clone_start.elapsed().as_secs_f64();
+                (false, path, Some(repo))
+            }
+        };
+
+        let collected =
+            collect_repository(local, &repo_dir, repo, &args, &stats, &multi_progress, None)?
+                .ok_or_else(|| anyhow::anyhow!("--dry-run and --list-only produce no bundle"))?;
+        let content = String::from_utf8(collected.render(&args)?).map_err(anyhow::Error::from)?;
+        Ok(Bundle {
+            content,
+            files: collected
+                .readme
+                .into_iter()
+                .chain(collected.files)
+                .collect(),
+            stats: stats.into_inner(),
+        })
+    }
+}
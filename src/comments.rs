@@ -0,0 +1,317 @@
+use std::path::Path;
+
+/// Comment and string-literal rules for the languages `--strip-comments` understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    /// `//` and `/* */`; `'` char literals and `` ` `` raw strings (C, C++, C#, Java, Go, Kotlin, Swift, Scala)
+    C,
+    /// Like C, but block comments nest, `'` may start a lifetime, and `r#"…"#` strings are raw
+    Rust,
+    /// Like C, with `'` strings and multi-line `` ` `` template literals
+    JavaScript,
+    /// `#` comments; `'`/`"` strings, including triple-quoted ones (Python, Ruby)
+    Python,
+    /// `#` comments only at the start of a word; `'` strings have no escapes
+    Shell,
+}
+
+fn syntax_for(path: &Path) -> Option<Syntax> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "rs" => Syntax::Rust,
+        "c" | "h" | "cpp" | "hpp" | "cc" | "cxx" | "cs" | "java" | "go" | "kt" | "kts"
+        | "swift" | "scala" => Syntax::C,
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => Syntax::JavaScript,
+        "py" | "pyi" | "rb" => Syntax::Python,
+        "sh" | "bash" | "zsh" => Syntax::Shell,
+        _ => return None,
+    })
+}
+
+/// Remove comments from `source` (`--strip-comments`), leaving string literals intact.
+/// Lines that held only a comment are dropped. `None` for languages without a stripper.
+pub fn strip_comments(path: &Path, source: &str) -> Option<String> {
+    let syntax = syntax_for(path)?;
+    let mut stripper = Stripper {
+        syntax,
+        chars: source.chars().collect(),
+        i: 0,
+        out: String::with_capacity(source.len()),
+        line_start: 0,
+        line_had_comment: false,
+    };
+    stripper.run();
+    Some(stripper.out)
+}
+
+struct Stripper {
+    syntax: Syntax,
+    chars: Vec<char>,
+    i: usize,
+    out: String,
+    /// Byte offset in `out` where the current line begins
+    line_start: usize,
+    line_had_comment: bool,
+}
+
+impl Stripper {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.i + offset).copied()
+    }
+
+    fn run(&mut self) {
+        let c_like = matches!(self.syntax, Syntax::C | Syntax::Rust | Syntax::JavaScript);
+        while let Some(c) = self.peek(0) {
+            match c {
+                '\n' => {
+                    self.i += 1;
+                    self.end_line();
+                }
+                '/' if c_like && self.peek(1) == Some('/') => self.skip_line_comment(),
+                '/' if c_like && self.peek(1) == Some('*') => self.skip_block_comment(),
+                '#' if self.starts_hash_comment() => self.skip_line_comment(),
+                '"' => self.copy_string('"'),
+                '`' if matches!(self.syntax, Syntax::C | Syntax::JavaScript) => {
+                    self.copy_string('`')
+                }
+                '\'' => self.copy_single_quote(),
+                'r' | 'b' if self.syntax == Syntax::Rust && self.raw_string_hashes().is_some() => {
+                    self.copy_raw_string()
+                }
+                _ => {
+                    self.out.push(c);
+                    self.i += 1;
+                }
+            }
+        }
+        if self.line_had_comment {
+            self.trim_line();
+        }
+    }
+
+    fn starts_hash_comment(&self) -> bool {
+        match self.syntax {
+            Syntax::Python | Syntax::Shell => {}
+            _ => return false,
+        }
+        // Keep the shebang
+        if self.i == 0 && self.peek(1) == Some('!') {
+            return false;
+        }
+        // In shell `$#` and `${#var}` are not comments
+        self.syntax == Syntax::Python
+            || self.i == 0
+            || self.chars[self.i - 1].is_whitespace()
+            || self.chars[self.i - 1] == ';'
+    }
+
+    fn skip_line_comment(&mut self) {
+        while self.peek(0).is_some_and(|c| c != '\n') {
+            self.i += 1;
+        }
+        self.line_had_comment = true;
+    }
+
+    fn skip_block_comment(&mut self) {
+        self.i += 2;
+        let mut depth = 1;
+        while depth > 0 {
+            match (self.peek(0), self.peek(1)) {
+                (None, _) => break,
+                (Some('*'), Some('/')) => {
+                    depth -= 1;
+                    self.i += 2;
+                }
+                (Some('/'), Some('*')) if self.syntax == Syntax::Rust => {
+                    depth += 1;
+                    self.i += 2;
+                }
+                _ => self.i += 1,
+            }
+        }
+        self.line_had_comment = true;
+    }
+
+    /// Copy a quoted literal verbatim, honouring backslash escapes.
+    fn copy_string(&mut self, quote: char) {
+        let triple = self.syntax == Syntax::Python
+            && self.peek(1) == Some(quote)
+            && self.peek(2) == Some(quote);
+        let delimiter_len = if triple { 3 } else { 1 };
+        let multiline = triple
+            || quote == '`'
+            || matches!(self.syntax, Syntax::Rust | Syntax::Shell) && quote == '"';
+        let escapes = !(self.syntax == Syntax::Shell && quote == '\'');
+
+        self.copy(delimiter_len);
+        while let Some(c) = self.peek(0) {
+            if c == '\\' && escapes {
+                self.copy(2);
+            } else if c == quote
+                && (!triple || (self.peek(1) == Some(quote) && self.peek(2) == Some(quote)))
+            {
+                self.copy(delimiter_len);
+                return;
+            } else if c == '\n' && !multiline {
+                // Unterminated; let the main loop resynchronise on the next line
+                return;
+            } else {
+                self.copy(1);
+            }
+        }
+    }
+
+    fn copy_single_quote(&mut self) {
+        if self.syntax != Syntax::Rust {
+            return self.copy_string('\'');
+        }
+        // `'x'` and `'\n'` are char literals; anything else is a lifetime or label
+        let is_char =
+            self.peek(1) == Some('\\') || (self.peek(1).is_some() && self.peek(2) == Some('\''));
+        if is_char {
+            self.copy_string('\'');
+        } else {
+            self.copy(1);
+        }
+    }
+
+    /// Number of `#`s if a Rust raw string (`r"`, `r#"`, `br"`, ...) starts here.
+    fn raw_string_hashes(&self) -> Option<usize> {
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+        if self.i > 0 && is_ident(self.chars[self.i - 1]) {
+            return None;
+        }
+        let mut offset = match (self.peek(0), self.peek(1)) {
+            (Some('r'), _) => 1,
+            (Some('b'), Some('r')) => 2,
+            _ => return None,
+        };
+        let mut hashes = 0;
+        while self.peek(offset) == Some('#') {
+            hashes += 1;
+            offset += 1;
+        }
+        (self.peek(offset) == Some('"')).then_some(hashes)
+    }
+
+    fn copy_raw_string(&mut self) {
+        let hashes = self.raw_string_hashes().unwrap_or(0);
+        // Prefix, hashes and opening quote
+        while self.peek(0) != Some('"') {
+            self.copy(1);
+        }
+        self.copy(1);
+        while let Some(c) = self.peek(0) {
+            self.copy(1);
+            if c == '"' && (0..hashes).all(|k| self.peek(k) == Some('#')) {
+                self.copy(hashes);
+                return;
+            }
+        }
+    }
+
+    fn copy(&mut self, n: usize) {
+        for _ in 0..n {
+            match self.peek(0) {
+                Some(c) => {
+                    self.out.push(c);
+                    self.i += 1;
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Drop trailing whitespace left by a removed comment; true if the line is now empty.
+    fn trim_line(&mut self) -> bool {
+        let trimmed = self.out[self.line_start..]
+            .trim_end_matches([' ', '\t', '\r'])
+            .len();
+        self.out.truncate(self.line_start + trimmed);
+        trimmed == 0
+    }
+
+    fn end_line(&mut self) {
+        let had_comment = std::mem::take(&mut self.line_had_comment);
+        if had_comment && self.trim_line() {
+            // The line held only a comment
+            return;
+        }
+        self.out.push('\n');
+        self.line_start = self.out.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(name: &str, source: &str) -> String {
+        strip_comments(Path::new(name), source).unwrap()
+    }
+
+    #[test]
+    fn c_like_comments_are_removed_but_strings_survive() {
+        let source = r##"// header
+/**
+ * Docs
+ */
+fn main() { // trailing
+    let url = "http://example.com"; /* inline */ let n = 1;
+    let s = "/* not a comment */ // nor this";
+    let c = '/'; let q = '"'; // after chars
+    let r = r#"raw // kept "quoted""#;
+    /* outer /* nested */ still comment */
+}
+"##;
+        assert_eq!(
+            strip("main.rs", source),
+            r##"fn main() {
+    let url = "http://example.com";  let n = 1;
+    let s = "/* not a comment */ // nor this";
+    let c = '/'; let q = '"';
+    let r = r#"raw // kept "quoted""#;
+}
+"##
+        );
+    }
+
+    #[test]
+    fn rust_lifetimes_are_not_char_literals() {
+        let source = "fn f<'a>(x: &'a str) -> &'a str { x } // c\n'outer: loop {} // d\n";
+        assert_eq!(
+            strip("lib.rs", source),
+            "fn f<'a>(x: &'a str) -> &'a str { x }\n'outer: loop {}\n"
+        );
+    }
+
+    #[test]
+    fn javascript_quotes_and_template_literals_are_kept() {
+        let source =
+            "const a = 'it\\'s // fine'; // gone\nconst t = `line // one\n${x} /* two */`;\n";
+        assert_eq!(
+            strip("app.ts", source),
+            "const a = 'it\\'s // fine';\nconst t = `line // one\n${x} /* two */`;\n"
+        );
+    }
+
+    #[test]
+    fn hash_comments_respect_strings_and_shebangs() {
+        let python = "#!/usr/bin/env python3\n# module comment\nx = \"#not\"  # note\n'''doc # kept\n'''\ny = 'a\\'#b'\n";
+        assert_eq!(
+            strip("tool.py", python),
+            "#!/usr/bin/env python3\nx = \"#not\"\n'''doc # kept\n'''\ny = 'a\\'#b'\n"
+        );
+
+        let shell = "echo \"$#\" ${#arr[@]} # count\necho 'it''s #1' # quoted\n";
+        assert_eq!(
+            strip("run.sh", shell),
+            "echo \"$#\" ${#arr[@]}\necho 'it''s #1'\n"
+        );
+    }
+
+    #[test]
+    fn unknown_languages_are_left_alone() {
+        assert_eq!(strip_comments(Path::new("notes.md"), "# Title"), None);
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::{Context, Result};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{parse_commit_backend, parse_repo_type, Args};
+
+pub const LOCAL_CONFIG_FILE: &str = "repod.toml";
+
+/// Persistent defaults read from `repod.toml` (current directory) and
+/// `~/.config/repod/config.toml`. Keys mirror the long CLI flag names.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FileConfig {
+    pub output_dir: Option<String>,
+    pub repo_types: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub only: Option<Vec<String>>,
+    pub only_dir: Option<Vec<String>>,
+    pub copy: Option<bool>,
+    pub write: Option<bool>,
+    pub dedupe: Option<bool>,
+    pub redact_secrets: Option<bool>,
+    pub concurrency: Option<usize>,
+    pub commit_backend: Option<String>,
+    pub commit_model: Option<String>,
+}
+
+impl FileConfig {
+    pub fn load(path: &Path) -> Result<Option<FileConfig>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        let config = toml::from_str(&text)
+            .with_context(|| format!("Invalid config file: {}", path.display()))?;
+        Ok(Some(config))
+    }
+
+    /// Field-by-field merge where values set in `self` win over `base`.
+    pub fn over(self, base: FileConfig) -> FileConfig {
+        FileConfig {
+            output_dir: self.output_dir.or(base.output_dir),
+            repo_types: self.repo_types.or(base.repo_types),
+            exclude: self.exclude.or(base.exclude),
+            only: self.only.or(base.only),
+            only_dir: self.only_dir.or(base.only_dir),
+            copy: self.copy.or(base.copy),
+            write: self.write.or(base.write),
+            dedupe: self.dedupe.or(base.dedupe),
+            redact_secrets: self.redact_secrets.or(base.redact_secrets),
+            concurrency: self.concurrency.or(base.concurrency),
+            commit_backend: self.commit_backend.or(base.commit_backend),
+            commit_model: self.commit_model.or(base.commit_model),
+        }
+    }
+
+    /// Fill `args` from this config wherever the flag was not given on the command line.
+    pub fn apply(self, args: &mut Args, matches: &ArgMatches) -> Result<()> {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        if let Some(v) = self.output_dir.filter(|_| !from_cli("output_dir")) {
+            args.output_dir = v;
+        }
+        if let Some(v) = self.repo_types.filter(|_| !from_cli("repo_types")) {
+            args.repo_types = v
+                .iter()
+                .map(|t| parse_repo_type(t).map_err(anyhow::Error::msg))
+                .collect::<Result<_>>()?;
+        }
+        if let Some(v) = self.exclude.filter(|_| !from_cli("exclude")) {
+            args.exclude = v;
+        }
+        if let Some(v) = self.only.filter(|_| !from_cli("only")) {
+            args.only = v;
+        }
+        if let Some(v) = self.only_dir.filter(|_| !from_cli("only_dirs")) {
+            args.only_dirs = v;
+        }
+        // `--copy` and `--write` pick one output mode, so either on the CLI overrides both
+        if !from_cli("copy") && !from_cli("write") {
+            args.copy = self.copy.unwrap_or(args.copy);
+            args.write = self.write.unwrap_or(args.write);
+        }
+        if let Some(v) = self.dedupe.filter(|_| !from_cli("dedupe")) {
+            args.dedupe = v;
+        }
+        if let Some(v) = self.redact_secrets.filter(|_| !from_cli("redact_secrets")) {
+            args.redact_secrets = v;
+        }
+        if let Some(v) = self.concurrency.filter(|_| !from_cli("concurrency")) {
+            args.concurrency = Some(v);
+        }
+        if let Some(v) = self.commit_backend.filter(|_| !from_cli("commit_backend")) {
+            args.commit_backend = parse_commit_backend(&v).map_err(anyhow::Error::msg)?;
+        }
+        if let Some(v) = self.commit_model.filter(|_| !from_cli("commit_model")) {
+            args.commit_model = Some(v);
+        }
+        Ok(())
+    }
+}
+
+pub fn global_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("repod").join("config.toml"))
+}
+
+/// Apply config files with precedence: CLI > local repod.toml > global config > built-in defaults.
+pub fn apply_config_files(args: &mut Args, matches: &ArgMatches) -> Result<()> {
+    let global = match global_config_path() {
+        Some(path) => FileConfig::load(&path)?.unwrap_or_default(),
+        None => FileConfig::default(),
+    };
+    let local = FileConfig::load(Path::new(LOCAL_CONFIG_FILE))?.unwrap_or_default();
+    local.over(global).apply(args, matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn args_from(argv: &[&str]) -> (Args, ArgMatches) {
+        let matches = Args::command().get_matches_from(argv);
+        let args = Args::from_arg_matches(&matches).expect("args");
+        (args, matches)
+    }
+
+    #[test]
+    fn cli_flags_override_local_and_local_overrides_global() {
+        let global: FileConfig =
+            toml::from_str("exclude = [\"global/\"]\nonly = [\"*.md\"]\ndedupe = true").unwrap();
+        let local: FileConfig =
+            toml::from_str("exclude = [\"local/\"]\noutput-dir = \"bundles\"").unwrap();
+        let (mut args, matches) = args_from(&["repod", "--only", "*.rs"]);
+        local.over(global).apply(&mut args, &matches).unwrap();
+
+        assert_eq!(args.exclude, vec!["local/".to_string()]);
+        assert_eq!(args.only, vec!["*.rs".to_string()]);
+        assert_eq!(args.output_dir, "bundles");
+        assert!(args.dedupe);
+    }
+
+    #[test]
+    fn cli_output_mode_overrides_config_mode() {
+        let config: FileConfig = toml::from_str("copy = true").unwrap();
+        let (mut args, matches) = args_from(&["repod", "--write"]);
+        config.apply(&mut args, &matches).unwrap();
+        assert!(args.write);
+        assert!(!args.copy);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(toml::from_str::<FileConfig>("exlude = [\"x\"]").is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use std::path::Path;
+
+/// Files `--extract-docs` turns into text instead of skipping them as binary.
+pub fn is_document(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf") || ext.eq_ignore_ascii_case("docx"))
+}
+
+/// Plain text of a `.pdf` or `.docx` file (`--extract-docs`).
+#[cfg(feature = "docs")]
+pub fn extract_text(path: &Path) -> Result<String> {
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "pdf" => extract_pdf(path),
+        "docx" => extract_docx(path),
+        _ => anyhow::bail!("not a .pdf or .docx file"),
+    }
+}
+
+#[cfg(not(feature = "docs"))]
+pub fn extract_text(_path: &Path) -> Result<String> {
+    anyhow::bail!("repod was built without the `docs` feature")
+}
+
+#[cfg(feature = "docs")]
+fn extract_pdf(path: &Path) -> Result<String> {
+    // pdf-extract panics on some malformed files; treat that like any other failure
+    std::panic::catch_unwind(|| pdf_extract::extract_text(path))
+        .map_err(|_| anyhow::anyhow!("unreadable PDF"))?
+        .map_err(|e| anyhow::anyhow!("unreadable PDF: {}", e))
+}
+
+/// Paragraph text from `word/document.xml`, one line per paragraph.
+#[cfg(feature = "docs")]
+fn extract_docx(path: &Path) -> Result<String> {
+    use quick_xml::events::Event;
+    use std::io::BufReader;
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+    let document = archive.by_name("word/document.xml")?;
+    let mut reader = quick_xml::Reader::from_reader(BufReader::new(document));
+
+    let mut text = String::new();
+    let mut in_text = false;
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) if e.local_name().as_ref() == b"t" => in_text = true,
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => text.push('\n'),
+                _ => {}
+            },
+            Event::Empty(e) => match e.local_name().as_ref() {
+                b"tab" => text.push('\t'),
+                b"br" | b"cr" => text.push('\n'),
+                _ => {}
+            },
+            Event::Text(e) if in_text => text.push_str(&e.unescape()?),
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_pdf_and_docx_are_documents() {
+        assert!(is_document(Path::new("design/Spec.PDF")));
+        assert!(is_document(Path::new("notes.docx")));
+        assert!(!is_document(Path::new("notes.doc")));
+        assert!(!is_document(Path::new("pdf")));
+    }
+
+    #[cfg(feature = "docs")]
+    #[test]
+    fn docx_paragraphs_become_lines() {
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("design.docx");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        zip.start_file::<_, ()>("word/document.xml", Default::default())
+            .unwrap();
+        zip.write_all(
+            br#"<?xml version="1.0"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:p><w:r><w:t>Goals &amp; scope</w:t></w:r></w:p><w:p><w:r><w:t>Fast</w:t><w:tab/><w:t xml:space="preserve"> and small</w:t></w:r></w:p></w:body></w:document>"#,
+        )
+        .unwrap();
+        zip.finish().unwrap();
+
+        assert_eq!(
+            extract_text(&path).unwrap(),
+            "Goals & scope\nFast\t and small\n"
+        );
+    }
+}
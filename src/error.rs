@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Failure kinds from cloning, bundling and the AI commit flows, so callers can
+/// match on them. `main` still reports everything through anyhow.
+#[derive(Debug, Error)]
+pub enum RepodError {
+    /// git2 failed to clone or fetch for a reason other than authentication
+    #[error("Failed to access repository: {url}: Git error")]
+    Clone {
+        url: String,
+        #[source]
+        source: git2::Error,
+    },
+    /// `--clone-timeout` ran out, or a network read stalled for that long
+    #[error("Failed to access repository: {url}: {message}")]
+    CloneTimeout { url: String, message: String },
+    /// Missing or rejected credentials (token, SSH key); the message says how to fix it
+    #[error("{0}")]
+    Auth(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Not a git repository: {}", .0.display())]
+    NotAGitRepo(PathBuf),
+    /// `--fail-on-empty` and the filters left nothing to bundle
+    #[error("No files were bundled (--fail-on-empty)")]
+    NoFilesProcessed,
+    #[error(
+        "Invalid repository URL format: {0}\nURL must start with 'https://', 'ssh://' or 'git@'"
+    )]
+    InvalidUrl(String),
+    /// An AI backend could not be reached or returned nothing usable
+    #[error("{backend}: {message}")]
+    AiBackend {
+        backend: &'static str,
+        message: String,
+    },
+    /// The generated commit subject broke Conventional Commit rules under --strict-commit
+    #[error("Generated commit message rejected: {0}")]
+    InvalidCommitMessage(String),
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl RepodError {
+    pub fn ai(backend: &'static str, message: impl Into<String>) -> RepodError {
+        RepodError::AiBackend {
+            backend,
+            message: message.into(),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use chrono::DateTime;
+use git2::{Repository, Sort};
+use serde::Serialize;
+
+/// Most commits `--include-git-history` will list, however many are asked for.
+pub const MAX_HISTORY: u32 = 200;
+
+/// One line of the `<git_history>` block.
+#[derive(Debug, Serialize)]
+pub struct CommitSummary {
+    /// Abbreviated hash, as `git log --oneline` prints it
+    pub hash: String,
+    pub author: String,
+    /// Author date, YYYY-MM-DD
+    pub date: String,
+    pub subject: String,
+}
+
+/// The last `limit` commits reachable from HEAD, newest first. An unborn branch has none.
+pub fn recent_commits(repo: &Repository, limit: u32) -> Result<Vec<CommitSummary>> {
+    if repo.head().is_err() {
+        return Ok(Vec::new());
+    }
+    let mut walk = repo.revwalk()?;
+    walk.push_head()?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+    let mut commits = Vec::new();
+    for oid in walk.take(limit.min(MAX_HISTORY) as usize) {
+        let commit = repo.find_commit(oid?)?;
+        let hash = match commit.as_object().short_id() {
+            Ok(short) => short.as_str().unwrap_or_default().to_string(),
+            Err(_) => commit.id().to_string()[..7].to_string(),
+        };
+        let author = commit.author();
+        commits.push(CommitSummary {
+            hash,
+            author: author.name().unwrap_or("unknown").to_string(),
+            date: DateTime::from_timestamp(author.when().seconds(), 0)
+                .map(|time| time.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            subject: commit.summary().unwrap_or_default().to_string(),
+        });
+    }
+    Ok(commits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commits_are_listed_newest_first_up_to_the_limit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        assert!(recent_commits(&repo, 5).unwrap().is_empty());
+
+        let signature =
+            git2::Signature::new("Ada", "ada@example.com", &git2::Time::new(1_760_000_000, 0))
+                .unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let mut parent = None;
+        for subject in ["first", "second", "third"] {
+            let parents: Vec<git2::Commit> = parent.iter().cloned().collect();
+            let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+            let message = format!("{}\n\nbody", subject);
+            let oid = repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    &message,
+                    &tree,
+                    &parent_refs,
+                )
+                .unwrap();
+            parent = Some(repo.find_commit(oid).unwrap());
+        }
+
+        let commits = recent_commits(&repo, 2).unwrap();
+        let subjects: Vec<&str> = commits.iter().map(|c| c.subject.as_str()).collect();
+        assert_eq!(subjects, ["third", "second"]);
+        assert_eq!(commits[0].author, "Ada");
+        assert_eq!(commits[0].date, "2025-10-09");
+        assert!(commits[0].hash.len() >= 7);
+    }
+}
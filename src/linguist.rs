@@ -0,0 +1,87 @@
+use git2::{AttrCheckFlags, AttrValue, Repository};
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+
+const LINGUIST_ATTRIBUTES: [&str; 2] = ["linguist-generated", "linguist-vendored"];
+
+/// Skips files that `.gitattributes` marks `linguist-generated` or `linguist-vendored`,
+/// matching what GitHub hides from diffs and language stats (`--respect-linguist`).
+pub struct LinguistFilter {
+    // git2 handles are Send but not Sync; walker filters must be both
+    repo: Mutex<Repository>,
+    root: PathBuf,
+    // Location of `root` relative to the repository workdir
+    prefix: PathBuf,
+}
+
+impl LinguistFilter {
+    /// Wrap an already opened repository. Returns None for bare repositories or
+    /// when `root` is not inside the workdir.
+    pub fn new(repo: Repository, root: &Path) -> Option<LinguistFilter> {
+        let workdir = repo.workdir()?.canonicalize().ok()?;
+        let prefix = root
+            .canonicalize()
+            .ok()?
+            .strip_prefix(&workdir)
+            .ok()?
+            .to_path_buf();
+        Some(LinguistFilter {
+            repo: Mutex::new(repo),
+            root: root.to_path_buf(),
+            prefix,
+        })
+    }
+
+    /// Find the repository containing `root`, if any.
+    pub fn discover(root: &Path) -> Option<LinguistFilter> {
+        let repo = Repository::discover(root).ok()?;
+        LinguistFilter::new(repo, root)
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        if rel.as_os_str().is_empty() {
+            return false;
+        }
+        let rel = self.prefix.join(rel);
+        let repo = self.repo.lock();
+        LINGUIST_ATTRIBUTES.iter().any(|name| {
+            let value = repo
+                .get_attr_bytes(&rel, name, AttrCheckFlags::default())
+                .ok()
+                .flatten();
+            match AttrValue::from_bytes(value) {
+                AttrValue::True => true,
+                AttrValue::String(v) => v.eq_ignore_ascii_case("true"),
+                _ => false,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn generated_and_vendored_paths_are_excluded() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        fs::write(
+            dir.path().join(".gitattributes"),
+            "dist/** linguist-generated\n*.pb.go linguist-generated=true\nvendor/** linguist-vendored\nkeep.js -linguist-generated\n",
+        )
+        .unwrap();
+        let filter = LinguistFilter::new(repo, dir.path()).unwrap();
+
+        assert!(filter.is_excluded(&dir.path().join("dist/bundle.js")));
+        assert!(filter.is_excluded(&dir.path().join("api/user.pb.go")));
+        assert!(filter.is_excluded(&dir.path().join("vendor/lib/a.go")));
+        assert!(!filter.is_excluded(&dir.path().join("keep.js")));
+        assert!(!filter.is_excluded(&dir.path().join("src/main.go")));
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub included: bool,
+    pub reason: &'static str,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<usize>,
+}
+
+/// Include/skip decision for every walked file (`--manifest`), keyed by repo-relative
+/// path so repeated walks over the same tree collapse into one entry.
+#[derive(Default)]
+pub struct Manifest {
+    entries: Mutex<BTreeMap<String, ManifestEntry>>,
+}
+
+impl Manifest {
+    pub fn include(&self, path: &str, bytes: u64, tokens: usize) {
+        self.insert(ManifestEntry {
+            path: path.to_string(),
+            included: true,
+            reason: "included",
+            bytes,
+            tokens: Some(tokens),
+        });
+    }
+
+    pub fn skip(&self, path: &str, reason: &'static str, bytes: u64) {
+        self.insert(ManifestEntry {
+            path: path.to_string(),
+            included: false,
+            reason,
+            bytes,
+            tokens: None,
+        });
+    }
+
+    fn insert(&self, entry: ManifestEntry) {
+        self.entries.lock().insert(entry.path.clone(), entry);
+    }
+
+    /// Write the entries as a JSON array sorted by path.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let entries = self.entries.lock();
+        let list: Vec<&ManifestEntry> = entries.values().collect();
+        let file = crate::watch::create_output(path)
+            .with_context(|| format!("Failed to create manifest: {}", path.display()))?;
+        serde_json::to_writer_pretty(file, &list)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_sorted_and_deduplicated_by_path() {
+        let manifest = Manifest::default();
+        manifest.skip("vendor/", "excluded", 0);
+        manifest.include("src/main.rs", 120, 30);
+        manifest.skip("vendor/", "excluded", 0);
+        manifest.skip("logo.png", "binary", 2048);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("manifest.json");
+        manifest.write(&path).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let entries = json.as_array().unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["path"], "logo.png");
+        assert_eq!(entries[0]["reason"], "binary");
+        assert!(entries[0].get("tokens").is_none());
+        assert_eq!(entries[1]["included"], true);
+        assert_eq!(entries[1]["tokens"], 30);
+        assert_eq!(entries[2]["path"], "vendor/");
+    }
+}
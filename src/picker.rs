@@ -0,0 +1,298 @@
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{read, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Stylize,
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::io::{IsTerminal, Write};
+
+/// Lines above the list: title and key help.
+const HEADER_LINES: usize = 2;
+
+/// A file offered by `--interactive`, relative to the repo root.
+pub struct Candidate {
+    pub path: String,
+    pub size: u64,
+}
+
+/// One line of the picker: a file, or a directory covering the files after it.
+struct Row {
+    depth: usize,
+    name: String,
+    /// Indices into the candidate list this row toggles
+    files: std::ops::Range<usize>,
+    is_dir: bool,
+}
+
+/// Candidates sorted by path components, with a row for each directory above its files.
+fn build_rows(candidates: &mut [Candidate]) -> Vec<Row> {
+    candidates.sort_by(|a, b| a.path.split('/').cmp(b.path.split('/')));
+
+    let mut rows: Vec<Row> = Vec::new();
+    // Row index of each open directory, outermost first
+    let mut open: Vec<usize> = Vec::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        let parts: Vec<&str> = candidate.path.split('/').collect();
+        let dirs = &parts[..parts.len() - 1];
+
+        // Close directories this file is not under
+        let shared = open
+            .iter()
+            .zip(dirs)
+            .take_while(|(&row, dir)| rows[row].name == **dir)
+            .count();
+        for row in open.drain(shared..) {
+            rows[row].files.end = index;
+        }
+        for (depth, dir) in dirs.iter().enumerate().skip(shared) {
+            open.push(rows.len());
+            rows.push(Row {
+                depth,
+                name: dir.to_string(),
+                files: index..index,
+                is_dir: true,
+            });
+        }
+        rows.push(Row {
+            depth: dirs.len(),
+            name: parts[parts.len() - 1].to_string(),
+            files: index..index + 1,
+            is_dir: false,
+        });
+    }
+    for row in open {
+        rows[row].files.end = candidates.len();
+    }
+    rows
+}
+
+/// Selection state, one flag per candidate.
+struct Picker {
+    rows: Vec<Row>,
+    selected: Vec<bool>,
+    cursor: usize,
+    scroll: usize,
+}
+
+impl Picker {
+    fn toggle(&mut self, row: usize) {
+        let files = self.rows[row].files.clone();
+        let all = self.selected[files.clone()].iter().all(|&s| s);
+        self.selected[files].fill(!all);
+    }
+
+    fn mark(&self, row: &Row) -> &'static str {
+        let selected = self.selected[row.files.clone()]
+            .iter()
+            .filter(|&&s| s)
+            .count();
+        match selected {
+            0 => "[ ]",
+            n if n == row.files.len() => "[x]",
+            _ => "[~]",
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        let last = self.rows.len().saturating_sub(1) as isize;
+        self.cursor = (self.cursor as isize + delta).clamp(0, last) as usize;
+    }
+
+    fn draw(&mut self, out: &mut impl Write, candidates: &[Candidate]) -> Result<()> {
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        let (width, visible) = (
+            width as usize,
+            (height as usize).saturating_sub(HEADER_LINES).max(1),
+        );
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + visible {
+            self.scroll = self.cursor + 1 - visible;
+        }
+
+        let (count, bytes) = candidates
+            .iter()
+            .zip(&self.selected)
+            .filter(|(_, &s)| s)
+            .fold((0, 0), |(n, b), (c, _)| (n + 1, b + c.size));
+        queue!(out, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        write!(
+            out,
+            "{}\r\n{}",
+            format!(
+                "Select files to bundle ({}/{} files, {})",
+                count,
+                candidates.len(),
+                format_size(bytes)
+            )
+            .bold(),
+            "↑/↓ move  PgUp/PgDn scroll  space toggle  a all  n none  enter confirm  esc cancel"
+                .dark_grey()
+        )?;
+
+        for (index, row) in self.rows.iter().enumerate().skip(self.scroll).take(visible) {
+            let name = if row.is_dir {
+                format!("{}/", row.name)
+            } else {
+                row.name.clone()
+            };
+            let size = if row.is_dir {
+                String::new()
+            } else {
+                format_size(candidates[row.files.start].size)
+            };
+            let label = format!("{}{} {}", "  ".repeat(row.depth), self.mark(row), name);
+            let room = width.saturating_sub(size.len() + 1);
+            let mut line: String = label.chars().take(room).collect();
+            let pad = room.saturating_sub(line.chars().count());
+            line.push_str(&" ".repeat(pad));
+            line.push(' ');
+            line.push_str(&size);
+            if index == self.cursor {
+                write!(out, "\r\n{}", line.reverse())?;
+            } else {
+                write!(out, "\r\n{}", line)?;
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MB", b as f64 / 1024.0 / 1024.0),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    }
+}
+
+/// Restores the terminal however the picker exits.
+struct ScreenGuard;
+
+impl Drop for ScreenGuard {
+    fn drop(&mut self) {
+        execute!(std::io::stderr(), LeaveAlternateScreen, cursor::Show).ok();
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+/// Show a checkbox list of `candidates` (`--interactive`) and return the paths left
+/// selected. Everything starts selected; Esc cancels the run.
+pub fn pick_files(mut candidates: Vec<Candidate>) -> Result<HashSet<String>> {
+    // Repos processed in parallel take turns at the terminal
+    static TERMINAL: Mutex<()> = Mutex::new(());
+    let _turn = TERMINAL.lock();
+
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        anyhow::bail!("--interactive needs a terminal");
+    }
+    if candidates.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let rows = build_rows(&mut candidates);
+    let mut picker = Picker {
+        selected: vec![true; candidates.len()],
+        rows,
+        cursor: 0,
+        scroll: 0,
+    };
+
+    let mut out = std::io::stderr();
+    terminal::enable_raw_mode().map_err(|e| anyhow::anyhow!("failed to enable raw mode: {}", e))?;
+    let _guard = ScreenGuard;
+    execute!(out, EnterAlternateScreen, cursor::Hide)?;
+
+    loop {
+        picker.draw(&mut out, &candidates)?;
+        let page = (terminal::size().map(|(_, h)| h).unwrap_or(24) as isize
+            - HEADER_LINES as isize)
+            .max(1);
+        match read() {
+            Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => picker.move_cursor(-1),
+                KeyCode::Down | KeyCode::Char('j') => picker.move_cursor(1),
+                KeyCode::PageUp => picker.move_cursor(-page),
+                KeyCode::PageDown => picker.move_cursor(page),
+                KeyCode::Home => picker.cursor = 0,
+                KeyCode::End => picker.move_cursor(isize::MAX / 2),
+                KeyCode::Char(' ') => picker.toggle(picker.cursor),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    anyhow::bail!("File selection cancelled")
+                }
+                KeyCode::Char('a') => picker.selected.fill(true),
+                KeyCode::Char('n') => picker.selected.fill(false),
+                KeyCode::Enter => break,
+                KeyCode::Esc | KeyCode::Char('q') => anyhow::bail!("File selection cancelled"),
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => return Err(anyhow::anyhow!("failed to read key: {}", e)),
+        }
+    }
+
+    Ok(candidates
+        .into_iter()
+        .zip(picker.selected)
+        .filter_map(|(candidate, selected)| selected.then_some(candidate.path))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(paths: &[&str]) -> Vec<Candidate> {
+        paths
+            .iter()
+            .map(|path| Candidate {
+                path: path.to_string(),
+                size: 1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn directories_head_their_files_and_toggle_them_together() {
+        let mut files = candidates(&["src/b/c.rs", "README.md", "src/a.rs", "src-x/d.rs"]);
+        let rows = build_rows(&mut files);
+        let lines: Vec<String> = rows
+            .iter()
+            .map(|row| format!("{}{}", "  ".repeat(row.depth), row.name))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "README.md",
+                "src",
+                "  a.rs",
+                "  b",
+                "    c.rs",
+                "src-x",
+                "  d.rs"
+            ]
+        );
+
+        let mut picker = Picker {
+            selected: vec![true; files.len()],
+            rows,
+            cursor: 0,
+            scroll: 0,
+        };
+        // Deselecting `src` clears both files beneath it, nested ones included
+        picker.toggle(1);
+        assert_eq!(picker.selected, [true, false, false, true]);
+        assert_eq!(picker.mark(&picker.rows[1]), "[ ]");
+
+        // A partly selected directory shows as such, and toggling it selects everything
+        picker.toggle(4);
+        assert_eq!(picker.mark(&picker.rows[1]), "[~]");
+        picker.toggle(1);
+        assert_eq!(picker.selected, [true; 4]);
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use git2::{Delta, DiffOptions, Repository};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Files that differ between a git ref and the working tree (`--since <ref>`),
+/// relative to the directory being bundled.
+pub struct ChangedFiles {
+    root: PathBuf,
+    files: HashSet<PathBuf>,
+    // Ancestors of changed files, so the walker keeps descending into them
+    dirs: HashSet<PathBuf>,
+}
+
+impl ChangedFiles {
+    /// Diff `rev` against the working tree (index and untracked files included).
+    /// Deleted files are left out since there is nothing to bundle.
+    pub fn compute(repo: &Repository, root: &Path, rev: &str) -> Result<ChangedFiles> {
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| anyhow::anyhow!("--since needs a repository with a working tree"))?
+            .canonicalize()?;
+        let prefix = root
+            .canonicalize()?
+            .strip_prefix(&workdir)
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        // Fresh clones only have the default branch locally, so fall back to origin/<rev>
+        let object = repo
+            .revparse_single(rev)
+            .or_else(|_| repo.revparse_single(&format!("origin/{}", rev)))
+            .with_context(|| format!("Failed to resolve --since ref: {}", rev))?;
+        let tree = object
+            .peel_to_tree()
+            .with_context(|| format!("--since ref does not point at a commit: {}", rev))?;
+
+        let mut opts = DiffOptions::new();
+        opts.include_untracked(true).recurse_untracked_dirs(true);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut opts))?;
+
+        let mut files = HashSet::new();
+        let mut dirs = HashSet::new();
+        for delta in diff.deltas() {
+            if delta.status() == Delta::Deleted {
+                continue;
+            }
+            let Some(path) = delta.new_file().path() else {
+                continue;
+            };
+            let Ok(rel) = path.strip_prefix(&prefix) else {
+                continue;
+            };
+            dirs.extend(
+                rel.ancestors()
+                    .skip(1)
+                    .filter(|a| !a.as_os_str().is_empty())
+                    .map(Path::to_path_buf),
+            );
+            files.insert(rel.to_path_buf());
+        }
+
+        Ok(ChangedFiles {
+            root: root.to_path_buf(),
+            files,
+            dirs,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Whether the walker should keep `path`: a changed file or a directory containing one.
+    pub fn keeps(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        if rel.as_os_str().is_empty() {
+            return true;
+        }
+        if is_dir {
+            self.dirs.contains(rel)
+        } else {
+            self.files.contains(rel)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn only_files_changed_since_ref_are_kept() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let repo = Repository::init(root).unwrap();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::write(root.join("src/lib.rs"), "old").unwrap();
+        fs::write(root.join("README.md"), "readme").unwrap();
+
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("t", "t@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+
+        fs::write(root.join("src/lib.rs"), "new").unwrap();
+        fs::write(root.join("src/nested/added.rs"), "added").unwrap();
+
+        let changed = ChangedFiles::compute(&repo, root, "HEAD").unwrap();
+        assert!(!changed.is_empty());
+        assert!(changed.keeps(&root.join("src/lib.rs"), false));
+        assert!(changed.keeps(&root.join("src/nested/added.rs"), false));
+        assert!(changed.keeps(&root.join("src/nested"), true));
+        assert!(!changed.keeps(&root.join("README.md"), false));
+        assert!(ChangedFiles::compute(&repo, root, "no-such-ref").is_err());
+    }
+}
@@ -1,25 +1,82 @@
 use anyhow::Result;
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use globset::GlobSet;
 use ignore::WalkBuilder;
 use std::collections::HashMap;
+use std::fs;
 use std::path::Path;
+use std::sync::Arc;
+
+use crate::linguist::LinguistFilter;
+use crate::since::ChangedFiles;
+use crate::{OnlyFilter, WalkSettings};
+
+/// Child shown under directories whose contents `--max-depth` left out.
+const ELIDED_MARKER: &str = "…";
+
+/// How tree branches are drawn (`--tree-style`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TreeStyle {
+    /// `├──`, `└──` and `│` box-drawing characters
+    #[default]
+    Unicode,
+    /// `|--`, `` `-- `` and `|`, for terminals and tools without box drawing
+    Ascii,
+    /// Two spaces per level and nothing else
+    Indent,
+}
+
+impl TreeStyle {
+    /// Drawn before a child: (entry, last entry, continuation below an entry, below the last).
+    fn glyphs(self) -> [&'static str; 4] {
+        match self {
+            TreeStyle::Unicode => ["├── ", "└── ", "│   ", "    "],
+            TreeStyle::Ascii => ["|-- ", "`-- ", "|   ", "    "],
+            TreeStyle::Indent => ["  "; 4],
+        }
+    }
+}
+
+/// Bundled files below a directory and their tokens (`--tree-annotate`).
+#[derive(Debug, Clone, Copy, Default)]
+struct NodeTotals {
+    files: usize,
+    tokens: usize,
+}
 
 pub struct DirectoryTree {
     name: String,
     children: Vec<DirectoryTree>,
     is_file: bool,
+    /// Set on every directory by `annotate`
+    totals: Option<NodeTotals>,
+}
+
+/// A lone `…` child when `dir` has contents the walk did not descend into.
+fn elided_marker(dir: &Path) -> Vec<DirectoryTree> {
+    if fs::read_dir(dir).is_ok_and(|mut d| d.next().is_some()) {
+        vec![DirectoryTree {
+            name: ELIDED_MARKER.to_string(),
+            children: Vec::new(),
+            is_file: true,
+            totals: None,
+        }]
+    } else {
+        Vec::new()
+    }
 }
 
 impl DirectoryTree {
     pub fn build(
         path: &Path,
         exclude_set: Option<&GlobSet>,
-        only_patterns: &[String],
-        only_dirs: &[String],
+        only_set: Option<&OnlyFilter>,
+        linguist: Option<&Arc<LinguistFilter>>,
+        since: Option<&Arc<ChangedFiles>>,
+        walk: WalkSettings,
     ) -> Result<DirectoryTree> {
         let root_name = path
             .file_name()
-            .unwrap_or_else(|| path.as_os_str())
+            .unwrap_or(path.as_os_str())
             .to_string_lossy()
             .to_string();
 
@@ -27,45 +84,12 @@ impl DirectoryTree {
             name: root_name,
             children: Vec::new(),
             is_file: false,
+            totals: None,
         };
 
         // Build a map of parent paths to their children
         let mut path_map: HashMap<String, Vec<DirectoryTree>> = HashMap::new();
 
-        // Build only-globset for file inclusion
-        let mut gs_builder = GlobSetBuilder::new();
-        let mut added = 0usize;
-        for d in only_dirs {
-            let d = d.trim_matches('/');
-            if !d.is_empty() {
-                let pat = format!("{}/**", d);
-                if let Ok(g) = Glob::new(&pat) {
-                    gs_builder.add(g);
-                    added += 1;
-                }
-            }
-        }
-        for p in only_patterns {
-            let p = p.trim();
-            if p.is_empty() {
-                continue;
-            }
-            let expanded = if p.contains('/') {
-                p.to_string()
-            } else {
-                format!("**/{}", p)
-            };
-            if let Ok(g) = Glob::new(&expanded) {
-                gs_builder.add(g);
-                added += 1;
-            }
-        }
-        let only_set: Option<GlobSet> = if added == 0 {
-            None
-        } else {
-            gs_builder.build().ok()
-        };
-
         // Build the walker with ignore support
         let mut walker_builder = WalkBuilder::new(path);
         walker_builder
@@ -75,6 +99,16 @@ impl DirectoryTree {
             .git_exclude(true)
             .ignore(true)
             .parents(true);
+        walk.apply(&mut walker_builder);
+        walker_builder.add_custom_ignore_filename(crate::REPOD_IGNORE_FILENAME);
+        crate::prune_excluded_entries(
+            &mut walker_builder,
+            path,
+            exclude_set,
+            linguist,
+            since,
+            None,
+        );
 
         // Collect all entries
         for entry in walker_builder
@@ -99,20 +133,12 @@ impl DirectoryTree {
                 }
 
                 // Check if it's a hidden file/folder (starts with .)
-                let is_hidden = entry_path.components().any(|component| {
-                    if let std::path::Component::Normal(name) = component {
-                        name.to_string_lossy().starts_with('.')
-                    } else {
-                        false
-                    }
-                });
-
-                if is_hidden {
+                if crate::is_hidden_below(entry_path, path) {
                     return false;
                 }
 
                 // Respect only globs for files (directories are kept; pruned later)
-                if let Some(ref set) = only_set {
+                if let Some(set) = only_set {
                     if let Ok(rel) = entry_path.strip_prefix(path) {
                         let rels = rel.to_string_lossy().replace('\\', "/");
                         let is_file = entry.file_type().map(|ft| ft.is_file()).unwrap_or(false);
@@ -134,22 +160,32 @@ impl DirectoryTree {
             let name = entry.file_name().to_string_lossy().to_string();
             let is_file = entry.file_type().map(|ft| ft.is_file()).unwrap_or(false);
 
+            // Directories at the depth limit are never walked; mark non-empty ones as elided
+            let children = if !is_file && walk.max_depth == Some(entry.depth()) {
+                elided_marker(entry_path)
+            } else {
+                Vec::new()
+            };
+
             let node = DirectoryTree {
                 name,
-                children: Vec::new(),
+                children,
                 is_file,
+                totals: None,
             };
 
             path_map.entry(parent_str).or_default().push(node);
         }
 
         // Build the tree recursively starting from root
+        if walk.max_depth == Some(0) {
+            root.children = elided_marker(path);
+        }
         root.build_recursive(path, &mut path_map);
 
-        // Prune empty directories if any inclusion filters are specified
-        if only_set.is_some() {
-            root.prune_empty_directories();
-        }
+        // Drop directories whose contents were all filtered out. With inclusion filters,
+        // directories that are empty on disk go too.
+        root.prune_empty_directories(path, only_set.is_none());
 
         root.sort_children();
 
@@ -174,17 +210,22 @@ impl DirectoryTree {
         }
     }
 
-    fn prune_empty_directories(&mut self) -> bool {
+    fn prune_empty_directories(&mut self, dir: &Path, keep_bare: bool) -> bool {
         if self.is_file {
             return true; // Files are always kept
         }
 
         // Recursively prune children and keep only non-empty ones
+        let had_children = !self.children.is_empty();
         self.children
-            .retain_mut(|child| child.prune_empty_directories());
+            .retain_mut(|child| child.prune_empty_directories(&dir.join(&child.name), keep_bare));
 
-        // A directory is kept if it has any children (files or non-empty directories)
+        // A directory is kept if it has any children (files or non-empty directories),
+        // or if nothing was filtered out of it because it is empty on disk
         !self.children.is_empty()
+            || (keep_bare
+                && !had_children
+                && fs::read_dir(dir).is_ok_and(|mut d| d.next().is_none()))
     }
 
     fn sort_children(&mut self) {
@@ -201,32 +242,278 @@ impl DirectoryTree {
         }
     }
 
-    pub fn format(&self) -> String {
+    /// Show `name` on the root line instead of the directory's own name (`--path-prefix`).
+    pub fn set_root_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    /// Count each bundled file and its tokens towards every directory above it, so directories
+    /// render as `name (N files, T tokens)`. Paths are relative to the root and `/`-separated.
+    pub fn annotate<'a>(&mut self, files: impl IntoIterator<Item = (&'a str, usize)>) {
+        self.reset_totals();
+        for (path, tokens) in files {
+            let mut components = path.split('/');
+            let mut node = &mut *self;
+            loop {
+                let totals = node.totals.get_or_insert_default();
+                totals.files += 1;
+                totals.tokens += tokens;
+                let Some(component) = components.next() else {
+                    break;
+                };
+                match node
+                    .children
+                    .iter_mut()
+                    .find(|child| !child.is_file && child.name == component)
+                {
+                    Some(child) => node = child,
+                    None => break,
+                }
+            }
+        }
+    }
+
+    /// Zero totals on every directory, so ones without bundled files still say so.
+    fn reset_totals(&mut self) {
+        if self.is_file {
+            return;
+        }
+        self.totals = Some(NodeTotals::default());
+        for child in &mut self.children {
+            child.reset_totals();
+        }
+    }
+
+    pub fn format(&self, style: TreeStyle) -> String {
         let mut output = String::new();
-        self.format_with_prefix("", "", &mut output);
+        self.format_with_prefix("", "", style, &mut output);
         output
     }
 
-    fn format_with_prefix(&self, prefix: &str, child_prefix: &str, output: &mut String) {
-        // Add root
-        output.push_str(&format!("{}{}\n", prefix, self.name));
+    fn format_with_prefix(
+        &self,
+        prefix: &str,
+        child_prefix: &str,
+        style: TreeStyle,
+        output: &mut String,
+    ) {
+        // Add root; the elision marker is the one non-ASCII name repod itself writes
+        let name = if style == TreeStyle::Ascii && self.name == ELIDED_MARKER {
+            "..."
+        } else {
+            &self.name
+        };
+        match self.totals {
+            Some(NodeTotals { files, tokens }) => output.push_str(&format!(
+                "{}{} ({} {}, {} tokens)\n",
+                prefix,
+                name,
+                files,
+                if files == 1 { "file" } else { "files" },
+                tokens
+            )),
+            None => output.push_str(&format!("{}{}\n", prefix, name)),
+        }
 
         // Add children
+        let [entry, last_entry, continuation, last_continuation] = style.glyphs();
         for (i, child) in self.children.iter().enumerate() {
             let is_last = i == self.children.len() - 1;
             let (next_prefix, next_child_prefix) = if is_last {
                 (
-                    format!("{}└── ", child_prefix),
-                    format!("{}    ", child_prefix),
+                    format!("{}{}", child_prefix, last_entry),
+                    format!("{}{}", child_prefix, last_continuation),
                 )
             } else {
                 (
-                    format!("{}├── ", child_prefix),
-                    format!("{}│   ", child_prefix),
+                    format!("{}{}", child_prefix, entry),
+                    format!("{}{}", child_prefix, continuation),
                 )
             };
 
-            child.format_with_prefix(&next_prefix, &next_child_prefix, output);
+            child.format_with_prefix(&next_prefix, &next_child_prefix, style, output);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn repos_under_dotted_directories_are_not_hidden() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join(".cache/repod/project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join(".github")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join(".github/ci.yml"), "").unwrap();
+
+        let rendered = DirectoryTree::build(&root, None, None, None, None, WalkSettings::default())
+            .unwrap()
+            .format(TreeStyle::Unicode);
+        assert_eq!(rendered, "project\n└── src\n    └── main.rs\n");
+    }
+
+    #[test]
+    fn max_depth_elides_deeper_directories() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("vendor/deep/deeper")).unwrap();
+        fs::create_dir_all(root.join("empty")).unwrap();
+        fs::write(root.join("vendor/deep/deeper/a.rs"), "").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+
+        let depth = |max_depth| WalkSettings {
+            max_depth: Some(max_depth),
+            ..WalkSettings::default()
+        };
+        let tree = DirectoryTree::build(root, None, None, None, None, depth(2)).unwrap();
+        let rendered = tree.format(TreeStyle::Unicode);
+        assert!(rendered.contains("├── empty\n"));
+        assert!(rendered.contains("└── deep\n│       └── …\n"));
+        assert!(!rendered.contains("deeper"));
+        assert!(rendered.contains("README.md"));
+
+        let flat = DirectoryTree::build(root, None, None, None, None, depth(0)).unwrap();
+        assert_eq!(
+            flat.format(TreeStyle::Unicode).lines().nth(1),
+            Some("└── …")
+        );
+    }
+
+    #[test]
+    fn directories_emptied_by_exclusions_are_pruned() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("docs/guide")).unwrap();
+        fs::create_dir_all(root.join("empty")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("docs/index.md"), "").unwrap();
+        fs::write(root.join("docs/guide/intro.md"), "").unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+
+        let mut builder = globset::GlobSetBuilder::new();
+        builder.add(globset::Glob::new("docs/**/*.md").unwrap());
+        let exclude = builder.build().unwrap();
+        let rendered = DirectoryTree::build(
+            root,
+            Some(&exclude),
+            None,
+            None,
+            None,
+            WalkSettings::default(),
+        )
+        .unwrap()
+        .format(TreeStyle::Unicode);
+        assert!(!rendered.contains("docs"));
+        assert!(!rendered.contains("guide"));
+        // Directories that were empty to begin with are still shown
+        assert!(rendered.contains("├── empty\n"));
+        assert!(rendered.contains("src\n    └── main.rs"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn followed_symlink_loops_terminate() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("shared")).unwrap();
+        fs::write(root.join("shared/util.rs"), "").unwrap();
+        std::os::unix::fs::symlink(root.join("shared"), root.join("src/linked")).unwrap();
+        // Points back at its own parent
+        std::os::unix::fs::symlink(root.join("src"), root.join("src/again")).unwrap();
+
+        let follow = WalkSettings {
+            follow_links: true,
+            ..WalkSettings::default()
+        };
+        let rendered = DirectoryTree::build(root, None, None, None, None, follow)
+            .unwrap()
+            .format(TreeStyle::Unicode);
+        assert!(rendered.contains("└── src\n    └── linked\n        └── util.rs"));
+        assert!(!rendered.contains("again"));
+
+        let plain = DirectoryTree::build(root, None, None, None, None, WalkSettings::default())
+            .unwrap()
+            .format(TreeStyle::Unicode);
+        assert_eq!(plain.matches("util.rs").count(), 1);
+    }
+
+    #[test]
+    fn tree_styles_draw_the_same_layout() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("project");
+        fs::create_dir_all(root.join("src/bin")).unwrap();
+        fs::write(root.join("src/bin/tool.rs"), "").unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+        let tree =
+            DirectoryTree::build(&root, None, None, None, None, WalkSettings::default()).unwrap();
+
+        assert_eq!(
+            tree.format(TreeStyle::Unicode),
+            "project\n\
+             ├── src\n\
+             │   ├── bin\n\
+             │   │   └── tool.rs\n\
+             │   └── lib.rs\n\
+             └── Cargo.toml\n"
+        );
+        assert_eq!(
+            tree.format(TreeStyle::Ascii),
+            "project\n\
+             |-- src\n\
+             |   |-- bin\n\
+             |   |   `-- tool.rs\n\
+             |   `-- lib.rs\n\
+             `-- Cargo.toml\n"
+        );
+        assert_eq!(
+            tree.format(TreeStyle::Indent),
+            "project\n  src\n    bin\n      tool.rs\n    lib.rs\n  Cargo.toml\n"
+        );
+
+        let shallow = WalkSettings {
+            max_depth: Some(1),
+            ..WalkSettings::default()
+        };
+        let elided = DirectoryTree::build(&root, None, None, None, None, shallow).unwrap();
+        assert!(elided
+            .format(TreeStyle::Ascii)
+            .contains("|-- src\n|   `-- ...\n"));
+    }
+
+    #[test]
+    fn annotations_sum_files_and_tokens_per_directory() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("project");
+        fs::create_dir_all(root.join("src/bin")).unwrap();
+        fs::create_dir_all(root.join("assets")).unwrap();
+        fs::write(root.join("src/bin/tool.rs"), "").unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        fs::write(root.join("assets/logo.png"), "").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+        let mut tree =
+            DirectoryTree::build(&root, None, None, None, None, WalkSettings::default()).unwrap();
+
+        tree.annotate([
+            ("README.md", 12),
+            ("src/bin/tool.rs", 30),
+            ("src/lib.rs", 100),
+        ]);
+        assert_eq!(
+            tree.format(TreeStyle::Unicode),
+            "project (3 files, 142 tokens)\n\
+             ├── assets (0 files, 0 tokens)\n\
+             │   └── logo.png\n\
+             ├── src (2 files, 130 tokens)\n\
+             │   ├── bin (1 file, 30 tokens)\n\
+             │   │   └── tool.rs\n\
+             │   └── lib.rs\n\
+             └── README.md\n"
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use anyhow::{Context, Result};
+use globset::GlobSet;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use crate::{is_excluded_path, is_hidden_below, normalize_rel_path, REPOD_IGNORE_FILENAME};
+
+/// Quiet period after the last event before a rebuild starts.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// How often the loop wakes up to check for Ctrl-C while idle.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Absolute paths of every file this process wrote output to (bundles, parts, manifests).
+/// Each rebuild writes them again, and those writes must not start another rebuild.
+static OUTPUTS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Create or truncate an output file, remembering it as repod's own.
+pub(crate) fn create_output(path: &Path) -> std::io::Result<File> {
+    note_output(path);
+    File::create(path)
+}
+
+/// Remember a file repod writes some other way (e.g. the `_latest` link).
+pub(crate) fn note_output(path: &Path) {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut outputs = OUTPUTS.lock();
+    if !outputs.contains(&path) {
+        outputs.push(path);
+    }
+}
+
+fn is_own_output(path: &Path) -> bool {
+    OUTPUTS.lock().iter().any(|output| output == path)
+}
+
+/// Decides which filesystem events should trigger a rebuild (`--watch`), using the
+/// same hidden-path rule, exclude globs and root ignore files as the bundling walker.
+pub struct WatchFilter {
+    root: PathBuf,
+    exclude_set: Option<GlobSet>,
+    ignores: Gitignore,
+}
+
+impl WatchFilter {
+    pub fn new(root: &Path, exclude_set: Option<GlobSet>) -> WatchFilter {
+        let mut builder = GitignoreBuilder::new(root);
+        for name in [".gitignore", ".ignore", REPOD_IGNORE_FILENAME] {
+            let path = root.join(name);
+            if path.is_file() {
+                // A malformed ignore file only weakens the filter; the walker reports it
+                let _ = builder.add(path);
+            }
+        }
+        WatchFilter {
+            root: root.to_path_buf(),
+            exclude_set,
+            ignores: builder.build().unwrap_or_else(|_| Gitignore::empty()),
+        }
+    }
+
+    pub fn is_relevant(&self, path: &Path) -> bool {
+        if !path.starts_with(&self.root) || path == self.root {
+            return false;
+        }
+        // Covers vim's .name.swp and emacs' .#name lock files as well
+        if is_hidden_below(path, &self.root) || is_editor_temp(path) || is_own_output(path) {
+            return false;
+        }
+        // Deleted paths have no metadata; treat them as files
+        let is_dir = path.is_dir();
+        let rel = normalize_rel_path(path, &self.root);
+        if self
+            .exclude_set
+            .as_ref()
+            .is_some_and(|set| is_excluded_path(set, &rel, is_dir))
+        {
+            return false;
+        }
+        !self
+            .ignores
+            .matched_path_or_any_parents(path, is_dir)
+            .is_ignore()
+    }
+
+    fn first_relevant<'a>(&self, event: &'a Event) -> Option<&'a Path> {
+        // Rebuilds read every file, so access events would retrigger forever
+        if matches!(event.kind, EventKind::Access(_)) {
+            return None;
+        }
+        event
+            .paths
+            .iter()
+            .map(PathBuf::as_path)
+            .find(|p| self.is_relevant(p))
+    }
+}
+
+/// Backup and autosave files editors write next to the file being saved
+/// (`name~`, `#name#`, `name.swp`).
+fn is_editor_temp(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    name.ends_with('~')
+        || (name.len() > 1 && name.starts_with('#') && name.ends_with('#'))
+        || path
+            .extension()
+            .is_some_and(|ext| matches!(ext.to_str(), Some("swp" | "swo" | "swx")))
+}
+
+/// Watch `root` and call `rebuild` after each debounced burst of relevant changes.
+/// Returns when Ctrl-C is pressed; rebuild errors are reported and watching continues.
+pub fn watch(root: &Path, filter: &WatchFilter, rebuild: impl FnMut() -> Result<()>) -> Result<()> {
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let stop = Arc::clone(&stop);
+        ctrlc::set_handler(move || stop.store(true, Ordering::SeqCst))
+            .context("Failed to install Ctrl-C handler")?;
+    }
+    watch_until(root, filter, &stop, rebuild)
+}
+
+/// The loop behind [`watch`], running until `stop` is set.
+fn watch_until(
+    root: &Path,
+    filter: &WatchFilter,
+    stop: &AtomicBool,
+    mut rebuild: impl FnMut() -> Result<()>,
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+    watcher
+        .watch(root, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", root.display()))?;
+    eprintln!("Watching {} for changes (Ctrl-C to stop)", root.display());
+
+    while !stop.load(Ordering::SeqCst) {
+        let changed = match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(event)) => match filter.first_relevant(&event) {
+                Some(path) => path.to_path_buf(),
+                None => continue,
+            },
+            Ok(Err(e)) => {
+                eprintln!("Watch error: {}", e);
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+
+        // Let editors and formatters finish writing before rebuilding
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+
+        eprintln!(
+            "\nChange detected: {}; rebuilding...",
+            normalize_rel_path(&changed, root)
+        );
+        if let Err(e) = rebuild() {
+            eprintln!("Rebuild failed: {:#}", e);
+        }
+    }
+
+    eprintln!("Stopped watching");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Instant;
+    use tempfile::TempDir;
+
+    #[test]
+    fn ignored_and_excluded_paths_do_not_trigger_rebuilds() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        let exclude_set = crate::build_exclude_globset(&["target/"], &["output/".to_string()]);
+        let filter = WatchFilter::new(root, exclude_set);
+
+        assert!(filter.is_relevant(&root.join("src/main.rs")));
+        assert!(!filter.is_relevant(&root.join("target/debug/repod")));
+        assert!(!filter.is_relevant(&root.join("output/repod.txt")));
+        assert!(!filter.is_relevant(&root.join("debug.log")));
+        assert!(!filter.is_relevant(root));
+    }
+
+    #[test]
+    fn hidden_paths_and_editor_temp_files_do_not_trigger_rebuilds() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let filter = WatchFilter::new(root, None);
+
+        assert!(!filter.is_relevant(&root.join("src/.main.rs.swp")));
+        assert!(!filter.is_relevant(&root.join("src/.#main.rs")));
+        assert!(!filter.is_relevant(&root.join(".git/index")));
+        assert!(!filter.is_relevant(&root.join("src/main.rs~")));
+        assert!(!filter.is_relevant(&root.join("src/#main.rs#")));
+        assert!(!filter.is_relevant(&root.join("notes.swp")));
+        assert!(filter.is_relevant(&root.join("src/main.rs")));
+    }
+
+    #[test]
+    fn rebuild_output_does_not_trigger_another_rebuild() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let filter = WatchFilter::new(&root, None);
+        let stop = AtomicBool::new(false);
+        let rebuilds = AtomicUsize::new(0);
+
+        std::thread::scope(|scope| {
+            let watcher = scope.spawn(|| {
+                watch_until(&root, &filter, &stop, || {
+                    rebuilds.fetch_add(1, Ordering::SeqCst);
+                    // A bundle and a manifest next to the sources, as with -o . --manifest
+                    create_output(&root.join("repo.txt"))?.write_all(b"bundle")?;
+                    create_output(&root.join("audit.json"))?.write_all(b"[]")?;
+                    Ok(())
+                })
+            });
+            std::thread::sleep(Duration::from_millis(500));
+            fs::write(root.join("src/main.rs"), "fn main() { }\n").unwrap();
+
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while rebuilds.load(Ordering::SeqCst) == 0 && Instant::now() < deadline {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            // Long enough for the written files' events to be seen and debounced
+            std::thread::sleep(DEBOUNCE * 3 + POLL_INTERVAL * 2);
+            stop.store(true, Ordering::SeqCst);
+            watcher.join().unwrap().unwrap();
+        });
+        assert_eq!(rebuilds.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,82 @@
+use git2::{Repository, Signature};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Run the repod binary on `root` with `--output-stdout`, ignoring any user config.
+fn run_to_stdout(root: &Path, flags: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_repod"))
+        .arg(root)
+        .args(["--output-stdout", "--no-config"])
+        .args(flags)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    output
+}
+
+/// Commit everything in `root` as a fresh repository.
+fn commit_all(root: &Path) {
+    let repo = Repository::init(root).unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Fixture", "fixture@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .unwrap();
+}
+
+#[test]
+fn warnings_stay_off_stdout() {
+    type Setup = fn(&Path);
+    let cases: &[(Setup, &[&str], &str)] = &[
+        (
+            |root| fs::write(root.join("bad.txt"), b"caf\xe9\n").unwrap(),
+            &[],
+            "1 file(s) are not valid UTF-8",
+        ),
+        (
+            |root| fs::write(root.join("b.txt"), "b\n").unwrap(),
+            &["--max-files", "1"],
+            "--max-files 1: left out 1 of 2 files",
+        ),
+        (
+            // Sparse, so the file costs no disk space
+            |root| {
+                fs::File::create(root.join("huge.log"))
+                    .unwrap()
+                    .set_len(65 * 1024 * 1024)
+                    .unwrap()
+            },
+            &[],
+            "Skipping huge.log (65 MB): larger than 64 MB",
+        ),
+        (|_| {}, &["--transform", "exit 3"], "--transform failed for"),
+        (
+            |_| {},
+            &["--include-git-history"],
+            "--include-git-history: not a git repository",
+        ),
+        (
+            commit_all,
+            &["--since", "HEAD"],
+            "No files changed since HEAD",
+        ),
+    ];
+
+    for (setup, flags, warning) in cases {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        setup(dir.path());
+
+        let output = run_to_stdout(dir.path(), flags);
+        let bundle = String::from_utf8(output.stdout).unwrap();
+        assert!(bundle.starts_with("<repository_metadata>"), "{}", bundle);
+        assert!(!bundle.contains(warning), "{:?}: {}", flags, bundle);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(warning), "{:?}: {}", flags, stderr);
+    }
+}
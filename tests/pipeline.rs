@@ -0,0 +1,209 @@
+use git2::{Repository, Signature};
+use repod::{Bundle, Input, OutputFormat, RepoType, Repod};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const PNG_HEADER: &[u8] = &[
+    0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', 0, 0, 0, 0x0d,
+];
+
+/// A committed repository with a README, sources in two languages, docs and a binary.
+fn fixture_repo() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    let files: &[(&str, &[u8])] = &[
+        ("README.md", b"# Fixture\n\nA small project.\n"),
+        ("src/main.rs", b"fn main() {\n    println!(\"hi\");\n}\n"),
+        (
+            "src/lib.rs",
+            b"pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+        ),
+        ("scripts/build.py", b"print('building')\n"),
+        ("docs/guide.md", b"# Guide\n\nUsage notes.\n"),
+    ];
+    for (path, contents) in files {
+        write(root, path, contents);
+    }
+    let mut logo = PNG_HEADER.to_vec();
+    logo.extend_from_slice(&[0u8; 256]);
+    write(root, "assets/logo.png", &logo);
+
+    let repo = Repository::init(root).unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Fixture", "fixture@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .unwrap();
+    dir
+}
+
+fn write(root: &Path, rel: &str, contents: &[u8]) {
+    let path = root.join(rel);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+fn bundle(repod: Repod, repo: &TempDir) -> Bundle {
+    repod
+        .process(Input::Path(repo.path().to_path_buf()))
+        .unwrap()
+}
+
+fn paths(bundle: &Bundle) -> BTreeSet<&str> {
+    bundle.files.iter().map(|f| f.path.as_str()).collect()
+}
+
+#[test]
+fn bundles_text_files_with_readme_first() {
+    let repo = fixture_repo();
+    let bundle = bundle(Repod::new(), &repo);
+
+    assert_eq!(bundle.files[0].path, "README.md");
+    assert_eq!(
+        paths(&bundle),
+        BTreeSet::from([
+            "README.md",
+            "docs/guide.md",
+            "scripts/build.py",
+            "src/lib.rs",
+            "src/main.rs",
+        ])
+    );
+    let readme_at = bundle.content.find("A small project.").unwrap();
+    let main_at = bundle.content.find("println!").unwrap();
+    assert!(readme_at < main_at);
+
+    assert!(bundle.stats.binary_files_skipped >= 1);
+    assert!(!bundle.content.contains("PNG"));
+    assert_eq!(bundle.stats.total_files, 5);
+    assert!(bundle.stats.total_tokens > 0);
+    assert!(bundle.files.iter().all(|f| f.token_count > 0));
+}
+
+#[test]
+fn tree_lists_the_repository_layout() {
+    let repo = fixture_repo();
+    let bundle = bundle(Repod::new(), &repo);
+
+    let tree = bundle
+        .content
+        .split("<directory_structure>\n")
+        .nth(1)
+        .and_then(|rest| rest.split("</directory_structure>").next())
+        .unwrap();
+    // The first line is the temp directory's random name
+    let lines: Vec<&str> = tree.trim_end().lines().skip(1).collect();
+    assert_eq!(
+        lines,
+        [
+            "├── assets",
+            "│   └── logo.png",
+            "├── docs",
+            "│   └── guide.md",
+            "├── scripts",
+            "│   └── build.py",
+            "├── src",
+            "│   ├── lib.rs",
+            "│   └── main.rs",
+            "└── README.md",
+        ]
+    );
+}
+
+#[test]
+fn only_patterns_select_matching_files() {
+    let repo = fixture_repo();
+    let bundle = bundle(Repod::new().only(["*.rs"]), &repo);
+    assert_eq!(
+        paths(&bundle),
+        BTreeSet::from(["src/lib.rs", "src/main.rs"])
+    );
+}
+
+#[test]
+fn only_dirs_and_patterns_must_both_match_in_files_and_tree() {
+    let repo = fixture_repo();
+    let main = bundle(Repod::new().only_dirs(["src"]).only(["main.rs"]), &repo);
+    assert_eq!(paths(&main), BTreeSet::from(["src/main.rs"]));
+    assert!(main
+        .content
+        .contains("└── src\n    └── main.rs\n\n</directory_structure>"));
+
+    let docs = bundle(Repod::new().only_dirs(["docs"]).only(["*.rs"]), &repo);
+    assert!(docs.files.is_empty());
+}
+
+#[test]
+fn exclude_patterns_remove_files_and_directories() {
+    let repo = fixture_repo();
+    let bundle = bundle(Repod::new().exclude(["docs/", "*.py"]), &repo);
+    assert_eq!(
+        paths(&bundle),
+        BTreeSet::from(["README.md", "src/lib.rs", "src/main.rs"])
+    );
+    assert!(!bundle.content.contains("guide.md"));
+}
+
+#[test]
+fn repo_types_keep_only_that_language() {
+    let repo = fixture_repo();
+    let rust = bundle(Repod::new().repo_types([RepoType::Rust]), &repo);
+    assert_eq!(
+        paths(&rust),
+        BTreeSet::from(["README.md", "src/lib.rs", "src/main.rs"])
+    );
+
+    let python = bundle(Repod::new().repo_types([RepoType::Python]), &repo);
+    assert_eq!(
+        paths(&python),
+        BTreeSet::from(["README.md", "scripts/build.py"])
+    );
+}
+
+#[test]
+fn formats_change_the_layout_but_not_the_files() {
+    let repo = fixture_repo();
+    let tagged = bundle(Repod::new(), &repo);
+    let xml = bundle(Repod::new().format(OutputFormat::Xml), &repo);
+
+    assert!(xml.content.contains("<source>src/main.rs</source>"));
+    assert!(!xml.content.contains("<file_info>"));
+    assert_eq!(paths(&tagged), paths(&xml));
+}
+
+#[test]
+fn versioned_json_matches_the_committed_schema() {
+    let repo = fixture_repo();
+    let bundle = bundle(
+        Repod::new().format(OutputFormat::Json).schema_version(1),
+        &repo,
+    );
+    let document: serde_json::Value = serde_json::from_str(&bundle.content).unwrap();
+
+    let schema: serde_json::Value =
+        serde_json::from_str(include_str!("../schema/bundle-v1.schema.json")).unwrap();
+    let validator = jsonschema::validator_for(&schema).unwrap();
+    let errors: Vec<String> = validator
+        .iter_errors(&document)
+        .map(|e| format!("{} at {}", e, e.instance_path))
+        .collect();
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    assert_eq!(document["schema_version"], 1);
+    assert_eq!(document["files"][0]["path"], "README.md");
+    assert_eq!(document["stats"]["files"], 5);
+    assert!(document["metadata"]["commit"].is_string());
+    assert!(document["tree"].as_str().unwrap().contains("main.rs"));
+
+    assert!(Repod::new()
+        .format(OutputFormat::Json)
+        .schema_version(2)
+        .process(Input::Path(repo.path().to_path_buf()))
+        .is_err());
+}